@@ -1,37 +1,105 @@
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::env;
-use std::fs::File;
-use std::io::{self, Write};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use env_logger::{Builder, Logger};
-use log::{LevelFilter, Metadata, Record};
+use log::{Level, LevelFilter, Metadata, Record};
 
 thread_local! {
-    static WRITER: RefCell<Option<io::BufWriter<File>>> = RefCell::new(None);
+    static WRITER: RefCell<Option<ThreadState>> = const { RefCell::new(None) };
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters::new()) };
+}
+
+/// Per-thread tally of `Warn`/`Error` records logged so far, independent of file rotation.
+#[derive(Clone, Copy, Default)]
+struct Counters {
+    warnings: u64,
+    errors: u64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Counters {
+            warnings: 0,
+            errors: 0,
+        }
+    }
+}
+
+/// Returns the number of `Warn`-level records logged so far on the calling thread.
+///
+/// Useful for a worker thread to report, at completion, whether it produced any warnings, without
+/// re-parsing its own log file.
+pub fn warning_count() -> u64 {
+    COUNTERS.with(|c| c.get().warnings)
+}
+
+/// Returns the number of `Error`-level records logged so far on the calling thread.
+///
+/// Useful for a worker thread to report, at completion, whether it produced any errors, without
+/// re-parsing its own log file.
+pub fn error_count() -> u64 {
+    COUNTERS.with(|c| c.get().errors)
 }
 
 static ALLOW_UNINITIALIZED: AtomicBool = AtomicBool::new(false);
 
+/// Per-thread mutable state: the active file writer, plus the bits of bookkeeping needed to
+/// decide when that file should be rotated.
+struct ThreadState {
+    writer: io::BufWriter<File>,
+    bytes_written: u64,
+    opened_at: Instant,
+    rotation_index: u64,
+}
+
+impl ThreadState {
+    fn new(writer: io::BufWriter<File>) -> Self {
+        ThreadState {
+            writer,
+            bytes_written: 0,
+            opened_at: Instant::now(),
+            rotation_index: 0,
+        }
+    }
+}
+
 /// Helper struct that can help retrieve a writer, from within a custom format function.
 ///
 /// Use `GetWriter::get()` to retrieve an instance of the writer.
 pub struct GetWriter<'a> {
-    rc: &'a RefCell<Option<io::BufWriter<File>>>,
+    rc: &'a RefCell<Option<ThreadState>>,
 }
 
 impl<'a> GetWriter<'a> {
     /// Retrieves a mutable reference to the underlying buffer writer.
     pub fn get(&self) -> RefMut<'a, io::BufWriter<File>> {
-        RefMut::map(self.rc.borrow_mut(), |maybe_buf_writer| {
-            maybe_buf_writer
+        RefMut::map(self.rc.borrow_mut(), |maybe_state| {
+            &mut maybe_state
                 .as_mut()
                 .expect("call the logger's initialize() function first")
+                .writer
         })
     }
 }
 
+/// Returns the calling thread's current position in its log file, flushing first so the result
+/// accounts for everything written so far (including by a custom `FormatFn`, which writes
+/// directly through `GetWriter` rather than `ThreadState::bytes_written`).
+fn stream_position(rc: &RefCell<Option<ThreadState>>) -> u64 {
+    let mut opt_state = rc.borrow_mut();
+    let state = opt_state
+        .as_mut()
+        .expect("call the logger's initialize() function first");
+    state.writer.stream_position().unwrap_or(0)
+}
+
 /// Format function to print logs in a custom format.
 ///
 /// Note: to allow for reentrant log invocations, `record.args()` must be reified before the writer
@@ -39,18 +107,212 @@ impl<'a> GetWriter<'a> {
 /// occur.
 pub type FormatFn = fn(&GetWriter, &Record) -> io::Result<()>;
 
+/// Built-in output formats, selectable through [`initialize_with_format`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The default, human-readable `"{level} - {args}"` format.
+    #[default]
+    Text,
+    /// One JSON object per line, Bunyan-style: `{"time":..,"level":..,"thread":..,"target":..,
+    /// "file":..,"line":..,"msg":..}`. Handy when per-thread files are ingested into a log
+    /// pipeline that expects machine-parseable records.
+    Json,
+}
+
+impl Format {
+    fn formatter(self) -> Option<FormatFn> {
+        match self {
+            Format::Text => None,
+            Format::Json => Some(json_format),
+        }
+    }
+}
+
+/// Built-in [`FormatFn`] backing [`Format::Json`].
+fn json_format(writer: &GetWriter, record: &Record) -> io::Result<()> {
+    // Reify the arguments (and anything else we pull off the record) before taking a hold on the
+    // writer, so that a reentrant log invocation doesn't double-borrow it.
+    let time = rfc3339_now();
+    let thread = thread_name_or_id();
+    let msg = escape_json(&format!("{}", record.args()));
+    let target = escape_json(record.target());
+    let file = record.file().map(escape_json);
+    let line = record.line();
+
+    let mut line_buf = String::with_capacity(128);
+    line_buf.push('{');
+    line_buf.push_str(&format!("\"time\":\"{}\",", time));
+    line_buf.push_str(&format!("\"level\":\"{}\",", record.level()));
+    line_buf.push_str(&format!("\"thread\":\"{}\",", escape_json(&thread)));
+    line_buf.push_str(&format!("\"target\":\"{}\",", target));
+    match file {
+        Some(file) => line_buf.push_str(&format!("\"file\":\"{}\",", file)),
+        None => line_buf.push_str("\"file\":null,"),
+    }
+    match line {
+        Some(line) => line_buf.push_str(&format!("\"line\":{},", line)),
+        None => line_buf.push_str("\"line\":null,"),
+    }
+    line_buf.push_str(&format!("\"msg\":\"{}\"", msg));
+    line_buf.push('}');
+
+    writeln!(writer.get(), "{}", line_buf)
+}
+
+/// Escapes a string for embedding as a JSON string value, without pulling in a JSON dependency.
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", ch as u32));
+            }
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Returns the calling thread's name, falling back to its ID when unnamed (mirrors the naming
+/// scheme used for the per-thread log files).
+fn thread_name_or_id() -> String {
+    let curthread = thread::current();
+    match curthread.name() {
+        Some(name) => name.to_owned(),
+        None => format!("{:?}", curthread.id()),
+    }
+}
+
+/// Formats the current wall-clock time as RFC3339 with millisecond precision (e.g.
+/// `"2024-06-01T12:00:00.123Z"`), without pulling in a heavyweight date/time dependency.
+fn rfc3339_now() -> String {
+    format_system_time(SystemTime::now())
+}
+
+fn format_system_time(time: SystemTime) -> String {
+    let duration = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0));
+    let secs = duration.as_secs();
+    let millis = duration.subsec_millis();
+
+    let days = secs / 86_400;
+    let secs_of_day = secs % 86_400;
+    let (year, month, day) = civil_from_days(days as i64);
+    let hour = secs_of_day / 3600;
+    let minute = (secs_of_day % 3600) / 60;
+    let second = secs_of_day % 60;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, hour, minute, second, millis
+    )
+}
+
+/// Converts a count of days since the Unix epoch (1970-01-01) into a (year, month, day) civil
+/// date. Adapted from Howard Hinnant's well-known `civil_from_days` algorithm.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = if m <= 2 { y + 1 } else { y };
+    (year, m, d)
+}
+
+/// Criteria governing when a thread's log file is rotated to a fresh file on disk.
+///
+/// A file is rotated as soon as either criterion is met; either one can be left unset to disable
+/// it.
+#[derive(Clone, Debug, Default)]
+pub struct RotationPolicy {
+    /// Rotate once the active file has received at least this many bytes.
+    pub max_bytes: Option<u64>,
+    /// Rotate once the active file has been open for at least this long.
+    pub max_age: Option<Duration>,
+    /// Retention policy applied to rotated files after each rotation. When unset, rotated files
+    /// are kept forever.
+    pub cleanup: Option<Cleanup>,
+}
+
+/// Retention policy applied to the rotated files left behind by a [`RotationPolicy`].
+#[derive(Clone, Copy, Debug)]
+pub struct Cleanup {
+    /// Number of rotated files to keep around, in addition to the currently active one. The
+    /// oldest rotated files beyond this count are deleted after each rotation.
+    pub keep: usize,
+}
+
+/// What to do when a thread's log file already exists on disk, e.g. from a previous run of the
+/// process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IfExists {
+    /// Discard the previous contents of the file. This is the historical behavior.
+    #[default]
+    Truncate,
+    /// Keep the previous contents, and append new records after them.
+    Append,
+    /// Don't write to the file at all. If the calling thread's file already exists when an
+    /// `initialize*` function opens it, that function returns `None` without installing the
+    /// logger and leaves the calling thread without a log file. When no earlier call installed
+    /// the logger, logging stays disabled for the process, the same way it would if `RUST_LOG`
+    /// weren't set.
+    Fail,
+}
+
+/// Options controlling how [`initialize_with_options`] sets up logging.
+#[derive(Default)]
+pub struct Options {
+    /// Custom format function; see [`FormatFn`]. Defaults to the built-in `"{level} - {args}"`
+    /// format.
+    pub formatter: Option<FormatFn>,
+    /// Log file rotation policy. Disabled (unbounded files) by default.
+    pub rotation: Option<RotationPolicy>,
+    /// Directory under which log files are created. Created with `create_dir_all` if it doesn't
+    /// exist yet. Defaults to the process' current working directory.
+    pub directory: Option<PathBuf>,
+    /// What to do when a thread's log file already exists. Defaults to [`IfExists::Truncate`].
+    pub if_exists: IfExists,
+    /// When set, records at or above this level are also written to the process' stderr, in
+    /// addition to the thread's file. Handy for interactive runs where a developer wants errors
+    /// surfaced immediately without tailing every thread's file. Disabled by default.
+    ///
+    /// When `formatter` is also set (including via [`Format::Json`]), the stderr mirror always
+    /// uses the plain `"{level} - {args}"` text rather than the custom format, since custom
+    /// formatters don't expose the bytes they wrote to the file for reuse.
+    pub duplicate_to_stderr: Option<LevelFilter>,
+    /// When set, the default text format is prefixed with an RFC3339 timestamp (millisecond
+    /// precision), e.g. `"2024-06-01T12:00:00.123Z INFO - message"`. Has no effect when
+    /// `formatter` is set. Disabled by default, to keep the historical `"{level} - {args}"`
+    /// format unchanged for existing consumers.
+    pub timestamp: bool,
+}
+
 /// Initializes the current process/thread with a logger, parsing the RUST_LOG environment
 /// variables to set the logging level filter and/or directives to set a filter by module name,
 /// following the usual env_logger conventions.
 ///
+/// Returns a [`ReconfHandle`] that can later be used to change the active level/filter spec, or
+/// `None` if `RUST_LOG` wasn't set and logging was left disabled.
+///
 /// Must be called on every running thread, or else logging will panic the first time it's used.
 /// ```
 /// use file_per_thread_logger::initialize;
 ///
 /// initialize("log-file-prefix");
 /// ```
-pub fn initialize(filename_prefix: &str) {
-    init_logging(filename_prefix, None)
+pub fn initialize(filename_prefix: &str) -> Option<ReconfHandle> {
+    init_logging(filename_prefix, Options::default())
 }
 
 /// Initializes the current process/thread with a logger, parsing the RUST_LOG environment
@@ -71,7 +333,7 @@ pub fn initialize(filename_prefix: &str) {
 ///     // Reify arguments first, to allow for recursive log invocations.
 ///     let args = format!("{}", record.args());
 ///     writeln!(
-///         writer,
+///         writer.get(),
 ///         "{} [{}:{}] {}",
 ///         record.level(),
 ///         record.file().unwrap_or_default(),
@@ -81,8 +343,72 @@ pub fn initialize(filename_prefix: &str) {
 /// };
 /// initialize_with_formatter("log-file-prefix", formatter);
 /// ```
-pub fn initialize_with_formatter(filename_prefix: &str, formatter: FormatFn) {
-    init_logging(filename_prefix, Some(formatter))
+pub fn initialize_with_formatter(
+    filename_prefix: &str,
+    formatter: FormatFn,
+) -> Option<ReconfHandle> {
+    init_logging(
+        filename_prefix,
+        Options {
+            formatter: Some(formatter),
+            ..Options::default()
+        },
+    )
+}
+
+/// Initializes the current process/thread with a logger, the same way [`initialize`] does, but
+/// selecting one of the built-in [`Format`]s instead of the default text format.
+///
+/// Must be called on every running thread, or else logging will panic the first time it's used.
+/// ```
+/// use file_per_thread_logger::{initialize_with_format, Format};
+///
+/// initialize_with_format("log-file-prefix", Format::Json);
+/// ```
+pub fn initialize_with_format(filename_prefix: &str, format: Format) -> Option<ReconfHandle> {
+    init_logging(
+        filename_prefix,
+        Options {
+            formatter: format.formatter(),
+            ..Options::default()
+        },
+    )
+}
+
+/// Initializes the current process/thread with a logger, the same way [`initialize`] does, but
+/// accepting a full set of [`Options`] (formatter, file rotation, output directory, etc).
+///
+/// Must be called on every running thread, or else logging will panic the first time it's used.
+/// ```
+/// use file_per_thread_logger::{initialize_with_options, IfExists, Options, RotationPolicy};
+/// use log::LevelFilter;
+/// use std::path::PathBuf;
+/// use std::time::Duration;
+///
+/// let handle = initialize_with_options(
+///     "log-file-prefix",
+///     Options {
+///         rotation: Some(RotationPolicy {
+///             max_bytes: Some(10 * 1024 * 1024),
+///             max_age: Some(Duration::from_secs(3600)),
+///             ..RotationPolicy::default()
+///         }),
+///         directory: Some(PathBuf::from("/var/log/my-service")),
+///         if_exists: IfExists::Append,
+///         duplicate_to_stderr: Some(LevelFilter::Warn),
+///         timestamp: true,
+///         ..Options::default()
+///     },
+/// );
+///
+/// // Later on, e.g. from a signal handler or an admin socket, raise the verbosity without
+/// // restarting the process.
+/// if let Some(handle) = handle {
+///     handle.set_filters("debug");
+/// }
+/// ```
+pub fn initialize_with_options(filename_prefix: &str, options: Options) -> Option<ReconfHandle> {
+    init_logging(filename_prefix, options)
 }
 
 /// Allow logs files to be created from threads in which the logger is specifically uninitialized.
@@ -93,46 +419,164 @@ pub fn allow_uninitialized() {
     ALLOW_UNINITIALIZED.store(true, Ordering::Relaxed);
 }
 
-fn init_logging(filename_prefix: &str, formatter: Option<FormatFn>) {
-    let env_var = env::var_os("RUST_LOG");
-    if env_var.is_none() {
-        return;
-    }
+fn init_logging(filename_prefix: &str, options: Options) -> Option<ReconfHandle> {
+    let env_var = env::var_os("RUST_LOG")?;
 
-    let logger = {
-        let mut builder = Builder::new();
-        builder.parse_filters(env_var.unwrap().to_str().unwrap());
-        builder.build()
+    // Every `initialize*` call shares one filter, so that the handle returned to any thread
+    // controls the logger that is actually installed for the process.
+    static SHARED_LOGGER: OnceLock<Arc<RwLock<Logger>>> = OnceLock::new();
+    let logger = SHARED_LOGGER
+        .get_or_init(|| {
+            let mut builder = Builder::new();
+            builder.parse_filters(env_var.to_str().unwrap());
+            Arc::new(RwLock::new(builder.build()))
+        })
+        .clone();
+    let handle = ReconfHandle {
+        logger: logger.clone(),
     };
 
-    // Ensure the thread local state is always properly initialized.
-    WRITER.with(|rc| {
+    // Ensure the thread local state is always properly initialized. If the file can't be opened
+    // (e.g. `IfExists::Fail` and the file already exists), bail out before installing the
+    // logger, the same way we do when `RUST_LOG` isn't set.
+    let opened = WRITER.with(|rc| -> io::Result<()> {
         if rc.borrow().is_none() {
-            rc.replace(Some(open_file(filename_prefix)));
+            let writer = open_file(
+                filename_prefix,
+                options.directory.as_deref(),
+                options.if_exists,
+            )?;
+            let mut state = ThreadState::new(writer);
+            state.rotation_index =
+                highest_rotation_index(filename_prefix, options.directory.as_deref());
+            rc.replace(Some(state));
         }
+        Ok(())
     });
+    opened.ok()?;
 
-    let logger = FilePerThreadLogger::new(logger, formatter);
+    let logger = FilePerThreadLogger::new(logger, filename_prefix.to_owned(), options);
     let _ =
         log::set_boxed_logger(Box::new(logger)).map(|()| log::set_max_level(LevelFilter::max()));
 
     log::info!("Set up logging; filename prefix is {}", filename_prefix);
+
+    Some(handle)
+}
+
+/// A handle letting callers change the active level/filter spec at runtime, without restarting
+/// the process. Returned by the `initialize*` functions.
+#[derive(Clone)]
+pub struct ReconfHandle {
+    logger: Arc<RwLock<Logger>>,
+}
+
+impl ReconfHandle {
+    /// Re-parses `filters` (an env_logger-style directive string, like the `RUST_LOG` value) and
+    /// atomically swaps it in as the active filter spec. Takes effect for every thread sharing
+    /// this handle's process-wide logger, immediately.
+    pub fn set_filters(&self, filters: &str) {
+        let mut builder = Builder::new();
+        builder.parse_filters(filters);
+        let new_logger = builder.build();
+        *self.logger.write().unwrap() = new_logger;
+    }
 }
 
 struct FilePerThreadLogger {
-    logger: Logger,
-    formatter: Option<FormatFn>,
+    logger: Arc<RwLock<Logger>>,
+    filename_prefix: String,
+    options: Options,
 }
 
 impl FilePerThreadLogger {
-    pub fn new(logger: Logger, formatter: Option<FormatFn>) -> Self {
-        FilePerThreadLogger { logger, formatter }
+    pub fn new(logger: Arc<RwLock<Logger>>, filename_prefix: String, options: Options) -> Self {
+        FilePerThreadLogger {
+            logger,
+            filename_prefix,
+            options,
+        }
+    }
+
+    fn open_file(&self) -> io::Result<io::BufWriter<File>> {
+        open_file(
+            &self.filename_prefix,
+            self.options.directory.as_deref(),
+            self.options.if_exists,
+        )
+    }
+
+    fn thread_file_path(&self) -> PathBuf {
+        thread_file_path(&self.filename_prefix, self.options.directory.as_deref())
+    }
+
+    /// Rotates the calling thread's file if the rotation policy's criteria are met. Must be
+    /// called before writing a record, and must not itself log anything, to avoid reentrancy
+    /// issues.
+    fn maybe_rotate(&self, state: &mut ThreadState) {
+        let Some(policy) = &self.options.rotation else {
+            return;
+        };
+
+        let should_rotate = policy
+            .max_bytes
+            .is_some_and(|max_bytes| state.bytes_written >= max_bytes)
+            || policy
+                .max_age
+                .is_some_and(|max_age| state.opened_at.elapsed() >= max_age);
+
+        if !should_rotate {
+            return;
+        }
+
+        let _ = state.writer.flush();
+
+        let current_path = self.thread_file_path();
+
+        // Find the next free index rather than trusting `state.rotation_index + 1` blindly: if
+        // another process (or a previous run of this one) already left a file at that index,
+        // renaming onto it would silently clobber it.
+        let mut next_index = state.rotation_index + 1;
+        let mut rotated_path = format!("{}.{}", current_path.display(), next_index);
+        while Path::new(&rotated_path).exists() {
+            next_index += 1;
+            rotated_path = format!("{}.{}", current_path.display(), next_index);
+        }
+
+        if fs::rename(&current_path, &rotated_path).is_err() {
+            // A transient I/O error (permission error, cross-device rename, disk full) shouldn't
+            // crash the calling thread; just keep writing to the current file and try rotating
+            // again on the next record.
+            return;
+        }
+
+        let writer = match self.open_file() {
+            Ok(writer) => writer,
+            Err(_) => {
+                // Couldn't reopen a fresh file after rotating the old one away (e.g.
+                // `IfExists::Fail` racing a concurrent writer for the same path). Degrade
+                // gracefully rather than panicking: keep using the existing, now-rotated-away
+                // writer, which is still safely on disk at `rotated_path`.
+                return;
+            }
+        };
+
+        *state = ThreadState::new(writer);
+        state.rotation_index = next_index;
+
+        if let Some(cleanup) = &policy.cleanup {
+            cleanup_rotated_files(
+                &self.filename_prefix,
+                self.options.directory.as_deref(),
+                cleanup.keep,
+            );
+        }
     }
 }
 
 impl log::Log for FilePerThreadLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        self.logger.enabled(metadata)
+        self.logger.read().unwrap().enabled(metadata)
     }
 
     fn log(&self, record: &Record) {
@@ -145,46 +589,114 @@ impl log::Log for FilePerThreadLogger {
                 // Initialize the logger with a default value, if it's not done yet.
                 let mut rc = rc.borrow_mut();
                 if rc.is_none() {
-                    *rc = Some(open_file(""));
+                    let writer = open_file(
+                        "",
+                        self.options.directory.as_deref(),
+                        self.options.if_exists,
+                    )
+                    .expect("Can't open tracing file");
+                    let mut state = ThreadState::new(writer);
+                    state.rotation_index =
+                        highest_rotation_index("", self.options.directory.as_deref());
+                    *rc = Some(state);
                 }
             }
 
-            if let Some(ref format_fn) = self.formatter {
+            {
+                let mut opt_state = rc.borrow_mut();
+                let state = opt_state
+                    .as_mut()
+                    .expect("call the logger's initialize() function first");
+                self.maybe_rotate(state);
+            }
+
+            match record.level() {
+                Level::Warn => COUNTERS.with(|c| {
+                    let mut counters = c.get();
+                    counters.warnings += 1;
+                    c.set(counters);
+                }),
+                Level::Error => COUNTERS.with(|c| {
+                    let mut counters = c.get();
+                    counters.errors += 1;
+                    c.set(counters);
+                }),
+                _ => {}
+            }
+
+            if let Some(ref format_fn) = self.options.formatter {
+                let before = stream_position(rc);
                 let get_writer = GetWriter { rc };
                 let _ = format_fn(&get_writer, record);
+                let after = stream_position(rc);
+
+                {
+                    let mut opt_state = rc.borrow_mut();
+                    let state = opt_state
+                        .as_mut()
+                        .expect("call the logger's initialize() function first");
+                    state.bytes_written += after.saturating_sub(before);
+                }
+
+                if self
+                    .options
+                    .duplicate_to_stderr
+                    .is_some_and(|threshold| record.level() <= threshold)
+                {
+                    // Custom formatters (including the built-in `Format::Json`) don't expose the
+                    // bytes they wrote to the file, so the stderr mirror falls back to the same
+                    // plain "{level} - {args}" text used by the default format.
+                    let line = format!("{} - {}\n", record.level(), record.args());
+                    let _ = io::stderr().write_all(line.as_bytes());
+                }
             } else {
                 // A note: we reify the argument first, before taking a hold on the mutable
                 // refcell, in case reifing args will cause a reentrant log invocation. Otherwise,
                 // we'd end up with a double borrow of the refcell.
                 let args = format!("{}", record.args());
+                let timestamp = self.options.timestamp.then(rfc3339_now);
 
-                let mut opt_writer = rc.borrow_mut();
-                let writer = opt_writer
+                let mut opt_state = rc.borrow_mut();
+                let state = opt_state
                     .as_mut()
                     .expect("call the logger's initialize() function first");
 
-                let _ = writeln!(*writer, "{} - {}", record.level(), args);
+                let line = match timestamp {
+                    Some(timestamp) => format!("{} {} - {}\n", timestamp, record.level(), args),
+                    None => format!("{} - {}\n", record.level(), args),
+                };
+                let _ = state.writer.write_all(line.as_bytes());
+                state.bytes_written += line.len() as u64;
+
+                if self
+                    .options
+                    .duplicate_to_stderr
+                    .is_some_and(|threshold| record.level() <= threshold)
+                {
+                    let _ = io::stderr().write_all(line.as_bytes());
+                }
             }
         })
     }
 
     fn flush(&self) {
         WRITER.with(|rc| {
-            let mut opt_writer = rc.borrow_mut();
-            let writer = opt_writer
+            let mut opt_state = rc.borrow_mut();
+            let state = opt_state
                 .as_mut()
                 .expect("call the logger's initialize() function first");
-            let _ = writer.flush();
+            let _ = state.writer.flush();
         });
     }
 }
 
-/// Open the tracing file for the current thread.
-fn open_file(filename_prefix: &str) -> io::BufWriter<File> {
+/// Builds the stem of the calling thread's log file name (prefix + thread name/id), with no
+/// directory component.
+fn thread_file_stem(filename_prefix: &str) -> String {
     let curthread = thread::current();
     let tmpstr;
-    let mut path = filename_prefix.to_owned();
-    path.extend(
+    let mut stem = filename_prefix.to_owned();
+    stem.extend(
         match curthread.name() {
             Some(name) => name.chars(),
             // The thread is unnamed, so use the thread ID instead.
@@ -195,6 +707,99 @@ fn open_file(filename_prefix: &str) -> io::BufWriter<File> {
         }
         .filter(|ch| ch.is_alphanumeric() || *ch == '-' || *ch == '_'),
     );
-    let file = File::create(path).expect("Can't open tracing file");
-    io::BufWriter::new(file)
+    stem
+}
+
+/// Builds the path of the calling thread's log file, without any rotation suffix.
+fn thread_file_path(filename_prefix: &str, directory: Option<&Path>) -> PathBuf {
+    let stem = thread_file_stem(filename_prefix);
+    match directory {
+        Some(directory) => directory.join(stem),
+        None => PathBuf::from(stem),
+    }
+}
+
+/// Lists the rotated files already on disk for the calling thread's stem, as `(index, path)`
+/// pairs, by globbing `directory` for `<stem>.N` entries.
+fn rotated_files(filename_prefix: &str, directory: Option<&Path>) -> Vec<(u64, PathBuf)> {
+    let file_stem = thread_file_stem(filename_prefix);
+    let dir = directory
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let rotated_suffix_of = |filename: &str| -> Option<u64> {
+        let rest = filename.strip_prefix(&file_stem)?.strip_prefix('.')?;
+        rest.parse::<u64>().ok()
+    };
+
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            rotated_suffix_of(&filename).map(|index| (index, entry.path()))
+        })
+        .collect()
+}
+
+/// Highest rotation index already present on disk for the calling thread's stem, or `0` if none
+/// exists yet. Used to seed `ThreadState::rotation_index` so that re-opening a file (process
+/// restart, or `IfExists::Append` picking up where a previous run left off) continues numbering
+/// rotated files instead of restarting at `.1` and clobbering a prior run's rotations.
+fn highest_rotation_index(filename_prefix: &str, directory: Option<&Path>) -> u64 {
+    rotated_files(filename_prefix, directory)
+        .into_iter()
+        .map(|(index, _)| index)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Deletes the oldest rotated files sharing `filename_prefix` plus the calling thread's stem,
+/// beyond `keep` of them.
+fn cleanup_rotated_files(filename_prefix: &str, directory: Option<&Path>, keep: usize) {
+    let mut rotated = rotated_files(filename_prefix, directory);
+
+    if rotated.len() <= keep {
+        return;
+    }
+
+    rotated.sort_by_key(|(index, _)| *index);
+    for (_, path) in &rotated[..rotated.len() - keep] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Open the tracing file for the current thread, under `directory` (the current working
+/// directory when unset), applying `if_exists` when the file is already present.
+///
+/// Returns an error if the directory couldn't be created, or if `if_exists` is [`IfExists::Fail`]
+/// and the file already exists.
+fn open_file(
+    filename_prefix: &str,
+    directory: Option<&Path>,
+    if_exists: IfExists,
+) -> io::Result<io::BufWriter<File>> {
+    if let Some(directory) = directory {
+        fs::create_dir_all(directory)?;
+    }
+    let path = thread_file_path(filename_prefix, directory);
+
+    let file = match if_exists {
+        IfExists::Truncate => File::create(&path)?,
+        IfExists::Append => {
+            // Start the stream position at the end of the prior contents, so that the bytes a
+            // custom formatter writes are counted without them.
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            file.seek(SeekFrom::End(0))?;
+            file
+        }
+        IfExists::Fail => OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&path)?,
+    };
+    Ok(io::BufWriter::new(file))
 }
@@ -0,0 +1,42 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, IfExists, Options};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+
+const LOG_PREFIX: &str = "append_test-";
+
+#[test]
+fn if_exists_append_preserves_prior_contents() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+
+    // Simulate a restart: a log file from a previous run already sits where this run's thread
+    // would write.
+    fs::write(temp_dir.path().join(&stem), "hello from a previous run\n")?;
+
+    env::set_var("RUST_LOG", "info");
+    let handle = initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            if_exists: IfExists::Append,
+            ..Options::default()
+        },
+    );
+    assert!(handle.is_some());
+
+    info!("hello from this run");
+    log::logger().flush();
+
+    let contents = fs::read_to_string(temp_dir.path().join(&stem))?;
+    assert!(contents.starts_with("hello from a previous run\n"));
+    assert!(contents.contains("hello from this run"));
+
+    temp_dir.close()?;
+    Ok(())
+}
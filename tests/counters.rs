@@ -0,0 +1,50 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{error_count, initialize, warning_count};
+
+use log::{error, info, warn};
+use std::env;
+use std::io;
+use std::thread;
+
+const LOG_PREFIX: &str = "counters_test-";
+
+#[test]
+fn per_thread_counters() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize(LOG_PREFIX);
+
+    assert_eq!(warning_count(), 0);
+    assert_eq!(error_count(), 0);
+
+    info!("not counted");
+    warn!("first warning");
+    warn!("second warning");
+    error!("first error");
+
+    assert_eq!(warning_count(), 2);
+    assert_eq!(error_count(), 1);
+
+    // A fresh thread starts with its own counters, independent of the main thread's tally.
+    thread::spawn(|| {
+        initialize(LOG_PREFIX);
+        assert_eq!(warning_count(), 0);
+        assert_eq!(error_count(), 0);
+        error!("helper thread error");
+        assert_eq!(warning_count(), 0);
+        assert_eq!(error_count(), 1);
+    })
+    .join()
+    .unwrap();
+
+    // The main thread's counters are unaffected by the helper thread's activity.
+    assert_eq!(warning_count(), 2);
+    assert_eq!(error_count(), 1);
+
+    log::logger().flush();
+    temp_dir.close()?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, IfExists, Options};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+
+const LOG_PREFIX: &str = "dir_test-";
+
+#[test]
+fn directory_and_if_exists() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+    let subdir = temp_dir.path().join("logs").join("nested");
+
+    // `IfExists::Fail` must leave logging disabled rather than panicking when the thread's file
+    // already exists in the target directory.
+    fs::create_dir_all(&subdir)?;
+    fs::write(subdir.join(&stem), "stale contents from a previous run\n")?;
+
+    env::set_var("RUST_LOG", "info");
+    let handle = initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            directory: Some(subdir.clone()),
+            if_exists: IfExists::Fail,
+            ..Options::default()
+        },
+    );
+    assert!(handle.is_none());
+    assert_eq!(
+        fs::read_to_string(subdir.join(&stem))?,
+        "stale contents from a previous run\n"
+    );
+
+    // Clear the conflicting file along with its directory, then initialize for real: the
+    // "nested" directory no longer exists and must be created, and the log file must land
+    // inside it.
+    fs::remove_dir_all(&subdir)?;
+    initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            directory: Some(subdir.clone()),
+            ..Options::default()
+        },
+    );
+
+    info!("hello from the configured directory");
+    log::logger().flush();
+
+    assert!(subdir.join(&stem).exists());
+    assert!(!temp_dir.path().join(&stem).exists());
+    let contents = fs::read_to_string(subdir.join(&stem))?;
+    assert!(contents.contains("hello from the configured directory"));
+
+    temp_dir.close()?;
+    Ok(())
+}
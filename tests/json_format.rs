@@ -0,0 +1,43 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_format, Format};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+
+const LOG_PREFIX: &str = "json_test-";
+
+#[test]
+fn json_format() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize_with_format(LOG_PREFIX, Format::Json);
+
+    info!("a message with a \"quote\" and a newline\nhere");
+    log::logger().flush();
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+    let contents = fs::read_to_string(&stem)?;
+    let lines: Vec<&str> = contents.lines().collect();
+
+    // The first line is the "Set up logging" record emitted by `init_logging` itself.
+    assert_eq!(lines.len(), 2);
+    for line in &lines {
+        assert!(line.starts_with('{') && line.ends_with('}'));
+        assert!(line.contains("\"level\":\"INFO\""));
+        assert!(line.contains("\"time\":\""));
+        assert!(line.contains("\"thread\":\""));
+    }
+
+    // Quotes and embedded newlines in the message must be escaped, so the record stays one
+    // valid JSON object per line.
+    assert!(lines[1].contains("\"target\":\"json_format\""));
+    assert!(lines[1].contains(r#""msg":"a message with a \"quote\" and a newline\nhere""#));
+
+    temp_dir.close()?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::initialize;
+
+use log::{debug, info};
+use std::env;
+use std::fs;
+use std::io;
+
+const LOG_PREFIX: &str = "reconf_test-";
+
+#[test]
+fn reconfigure_filters_at_runtime() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    let handle = initialize(LOG_PREFIX).expect("RUST_LOG is set");
+
+    debug!("first debug entry, should be filtered out");
+    info!("first info entry");
+    log::logger().flush();
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+    let contents = fs::read_to_string(&stem)?;
+    assert!(!contents.contains("first debug entry"));
+    assert!(contents.contains("first info entry"));
+
+    // Raising the verbosity at runtime, without re-initializing, must take effect immediately.
+    handle.set_filters("debug");
+
+    debug!("second debug entry, should now come through");
+    log::logger().flush();
+
+    let contents = fs::read_to_string(&stem)?;
+    assert!(contents.contains("second debug entry"));
+
+    temp_dir.close()?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, Cleanup, Options, RotationPolicy};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+
+// Use a prefix containing a dot, to make sure rotated file names are built by appending a
+// suffix rather than by replacing whatever follows the last dot in the stem.
+const LOG_PREFIX: &str = "rot.log-";
+
+fn rotated_files() -> io::Result<Vec<String>> {
+    let mut names: Vec<String> = fs::read_dir(env::current_dir()?)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(LOG_PREFIX))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[test]
+fn rotation_and_cleanup() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            rotation: Some(RotationPolicy {
+                max_bytes: Some(1),
+                cleanup: Some(Cleanup { keep: 2 }),
+                ..RotationPolicy::default()
+            }),
+            ..Options::default()
+        },
+    );
+
+    // Every record is at least a few bytes long, so a `max_bytes` of 1 rotates after each one.
+    for i in 0..8 {
+        info!("line {}", i);
+    }
+    log::logger().flush();
+
+    // libtest runs each test function on a thread named after the test itself.
+    let main_stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+
+    // The active file plus up to `keep` rotated files should remain; older rotated files must
+    // have been cleaned up, and none of their names should have been truncated at the dot in
+    // the prefix.
+    let files = rotated_files()?;
+    assert!(files.contains(&main_stem));
+    for name in &files {
+        assert!(
+            name.starts_with(&main_stem),
+            "rotated file {} lost the dotted prefix",
+            name
+        );
+    }
+
+    let rotated: Vec<&String> = files.iter().filter(|name| *name != &main_stem).collect();
+    assert_eq!(rotated.len(), 2);
+
+    // Rotation indices must be monotonically increasing, not reused, so the two survivors are
+    // the most recent rotations rather than both landing on the same `.1` suffix.
+    let mut indices: Vec<u64> = rotated
+        .iter()
+        .map(|name| name[main_stem.len() + 1..].parse().unwrap())
+        .collect();
+    indices.sort_unstable();
+    assert_eq!(indices, vec![indices[0], indices[0] + 1]);
+
+    temp_dir.close()?;
+    Ok(())
+}
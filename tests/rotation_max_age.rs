@@ -0,0 +1,62 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, Options, RotationPolicy};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+const LOG_PREFIX: &str = "rot_age-";
+
+#[test]
+fn rotation_by_max_age() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            rotation: Some(RotationPolicy {
+                max_age: Some(Duration::from_millis(10)),
+                ..RotationPolicy::default()
+            }),
+            ..Options::default()
+        },
+    );
+
+    info!("before the age threshold");
+    log::logger().flush();
+
+    thread::sleep(Duration::from_millis(50));
+
+    // This record is logged well after `max_age` has elapsed, so it must trigger a rotation
+    // before being written, leaving the first line behind in a rotated file.
+    info!("after the age threshold");
+    log::logger().flush();
+
+    let main_stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+
+    let mut names: Vec<String> = fs::read_dir(env::current_dir()?)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(LOG_PREFIX))
+        .collect();
+    names.sort();
+
+    let rotated: Vec<&String> = names.iter().filter(|name| *name != &main_stem).collect();
+    assert_eq!(rotated.len(), 1, "expected exactly one rotated file");
+
+    let rotated_contents = fs::read_to_string(temp_dir.path().join(rotated[0]))?;
+    assert!(rotated_contents.contains("before the age threshold"));
+
+    let current_contents = fs::read_to_string(temp_dir.path().join(&main_stem))?;
+    assert!(current_contents.contains("after the age threshold"));
+    assert!(!current_contents.contains("before the age threshold"));
+
+    temp_dir.close()?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, GetWriter, Options};
+
+use log::{info, warn, Record};
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+
+const LOG_PREFIX: &str = "stderr_test-";
+
+fn custom_format(writer: &GetWriter, record: &Record) -> io::Result<()> {
+    let args = format!("{}", record.args());
+    writeln!(writer.get(), "CUSTOM {} {}", record.level(), args)
+}
+
+// `duplicate_to_stderr` writes to the process' actual stderr, which isn't practical to capture
+// from a safe, dependency-free integration test. This exercises the code path that applies the
+// mirror after a custom formatter runs (the bug fixed in the review), and confirms the formatter
+// still writes the expected content to the thread's file regardless.
+#[test]
+fn stderr_mirror_with_custom_formatter() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            formatter: Some(custom_format),
+            duplicate_to_stderr: Some(log::LevelFilter::Warn),
+            ..Options::default()
+        },
+    );
+
+    info!("below the stderr threshold");
+    warn!("at the stderr threshold");
+    log::logger().flush();
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+    let contents = fs::read_to_string(&stem)?;
+    assert!(contents.contains("CUSTOM INFO below the stderr threshold"));
+    assert!(contents.contains("CUSTOM WARN at the stderr threshold"));
+
+    temp_dir.close()?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use tempfile::tempdir;
+
+use file_per_thread_logger::{initialize_with_options, Options};
+
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+
+const LOG_PREFIX: &str = "timestamp_test-";
+
+#[test]
+fn opt_in_timestamp_prefix() -> io::Result<()> {
+    let temp_dir = tempdir()?;
+    env::set_current_dir(&temp_dir)?;
+
+    env::set_var("RUST_LOG", "info");
+    initialize_with_options(
+        LOG_PREFIX,
+        Options {
+            timestamp: true,
+            ..Options::default()
+        },
+    );
+
+    info!("hello with a timestamp");
+    log::logger().flush();
+
+    let stem = format!("{}{}", LOG_PREFIX, std::thread::current().name().unwrap());
+    let contents = fs::read_to_string(&stem)?;
+    let line = contents
+        .lines()
+        .find(|line| line.contains("hello with a timestamp"))
+        .expect("message line");
+
+    let timestamp = line.split(' ').next().expect("timestamp token");
+    // RFC3339 with millisecond precision, e.g. "2024-06-01T12:00:00.123Z".
+    assert_eq!(timestamp.len(), "2024-06-01T12:00:00.123Z".len());
+    assert!(timestamp.ends_with('Z'));
+    assert_eq!(timestamp.as_bytes()[4], b'-');
+    assert_eq!(timestamp.as_bytes()[7], b'-');
+    assert_eq!(timestamp.as_bytes()[10], b'T');
+    assert!(line.ends_with("INFO - hello with a timestamp"));
+
+    temp_dir.close()?;
+    Ok(())
+}